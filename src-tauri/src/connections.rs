@@ -0,0 +1,151 @@
+//! Named connection registry for VizierCells.
+//!
+//! A single app instance can talk to several databases at once — SQLite files
+//! and Postgres servers. Each is registered under a user-chosen name and backed
+//! by an [`AnyPool`] so the rest of the backend runs one query path regardless
+//! of driver. The registry lives in Tauri managed state as a shared map guarded
+//! by an async `RwLock`.
+
+use sqlx::any::AnyPoolOptions;
+use sqlx::AnyPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// The backend driver behind a connection. sqlx 0.7's `Any` layer no longer
+/// exposes a runtime kind, so we record it ourselves from the connection URL and
+/// carry it alongside the pool.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Backend {
+    Sqlite,
+    Postgres,
+}
+
+/// A registered connection: its pool plus the backend it talks to. `AnyPool` is
+/// an `Arc` handle, so cloning a `Connection` is cheap.
+#[derive(Clone)]
+pub struct Connection {
+    pub pool: AnyPool,
+    pub backend: Backend,
+}
+
+/// Shared, mutable map of connection name to its pool.
+pub type ConnectionRegistry = Arc<RwLock<HashMap<String, Connection>>>;
+
+/// Build an empty registry for `.manage()`.
+pub fn new_registry() -> ConnectionRegistry {
+    Arc::new(RwLock::new(HashMap::new()))
+}
+
+/// Normalise a user-supplied kind string into the connection URL scheme we
+/// expect, so callers can pass either `"sqlite"`/`"postgres"` or a full URL.
+fn build_url(kind: &str, url: &str) -> Result<String, String> {
+    if url.contains("://") {
+        return Ok(url.to_string());
+    }
+    match kind.to_ascii_lowercase().as_str() {
+        "sqlite" => Ok(format!("sqlite://{}", url)),
+        "postgres" | "postgresql" => Ok(format!("postgres://{}", url)),
+        other => Err(format!("unsupported connection kind: {}", other)),
+    }
+}
+
+/// Register a new named connection, opening its pool eagerly so configuration
+/// errors surface at registration time rather than on first query.
+pub async fn add_connection(
+    registry: &ConnectionRegistry,
+    name: String,
+    kind: String,
+    url: String,
+) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("connection name must not be empty".to_string());
+    }
+    let dsn = build_url(&kind, &url)?;
+    let backend = if dsn.starts_with("postgres://") || dsn.starts_with("postgresql://") {
+        Backend::Postgres
+    } else {
+        Backend::Sqlite
+    };
+    let pool = AnyPoolOptions::new()
+        .max_connections(5)
+        .connect(&dsn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut map = registry.write().await;
+    if map.contains_key(&name) {
+        return Err(format!("a connection named '{}' already exists", name));
+    }
+    map.insert(name, Connection { pool, backend });
+    Ok(())
+}
+
+/// List the names of every registered connection, sorted for stable UI order.
+pub async fn list_connections(registry: &ConnectionRegistry) -> Vec<String> {
+    let map = registry.read().await;
+    let mut names: Vec<String> = map.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Remove a connection, closing its pool. Returns an error if it is unknown.
+pub async fn remove_connection(
+    registry: &ConnectionRegistry,
+    name: &str,
+) -> Result<(), String> {
+    let conn = {
+        let mut map = registry.write().await;
+        map.remove(name)
+    };
+    match conn {
+        Some(conn) => {
+            conn.pool.close().await;
+            Ok(())
+        }
+        None => Err(format!("no connection named '{}'", name)),
+    }
+}
+
+/// Fetch a clone of a registered connection by name. Both the pool (an `Arc`
+/// handle) and the backend tag are cheap to clone and can be held across the
+/// query without the lock.
+pub async fn pool_for(
+    registry: &ConnectionRegistry,
+    name: &str,
+) -> Result<Connection, String> {
+    let map = registry.read().await;
+    map.get(name)
+        .cloned()
+        .ok_or_else(|| format!("no connection named '{}'", name))
+}
+
+/// Rewrite positional placeholders to the syntax the target driver expects.
+///
+/// Queries are authored with `?` placeholders; SQLite accepts them as-is while
+/// Postgres needs ordinal `$1..$n` markers. Characters inside single-quoted
+/// string literals are left untouched so `'?'` in data is not mistaken for a
+/// bind marker.
+pub fn rewrite_placeholders(sql: &str, backend: Backend) -> String {
+    if backend != Backend::Postgres {
+        return sql.to_string();
+    }
+    let mut out = String::with_capacity(sql.len());
+    let mut in_string = false;
+    let mut next = 1;
+    for ch in sql.chars() {
+        match ch {
+            '\'' => {
+                in_string = !in_string;
+                out.push(ch);
+            }
+            '?' if !in_string => {
+                out.push('$');
+                out.push_str(&next.to_string());
+                next += 1;
+            }
+            _ => out.push(ch),
+        }
+    }
+    out
+}
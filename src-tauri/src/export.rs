@@ -0,0 +1,231 @@
+//! Export of materialized query results to interchange formats.
+//!
+//! The last result of each streamed query is retained in a small buffer keyed by
+//! `query_id` (see [`ResultStore`]). [`export_result`] re-serializes that buffer
+//! into CSV, newline-delimited JSON, or an Apache Arrow IPC file so downstream
+//! tools such as DuckDB or pandas can consume cell output directly.
+
+use arrow::array::{ArrayRef, BinaryBuilder, BooleanBuilder, Float64Builder, Int64Builder, StringBuilder};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Name and declared SQL type of a result column.
+#[derive(Clone)]
+pub struct ColumnMeta {
+    pub name: String,
+    pub type_name: String,
+}
+
+/// A materialized result retained for later export.
+#[derive(Clone)]
+pub struct StoredResult {
+    pub columns: Vec<ColumnMeta>,
+    pub rows: Vec<Map<String, Value>>,
+}
+
+/// Buffer of the most recent result per `query_id`.
+pub type ResultStore = Arc<Mutex<HashMap<String, StoredResult>>>;
+
+/// Build an empty result store for `.manage()`.
+pub fn new_store() -> ResultStore {
+    Arc::new(Mutex::new(HashMap::new()))
+}
+
+/// Record (replacing any prior) the materialized result for a query.
+pub async fn store(store: &ResultStore, query_id: String, result: StoredResult) {
+    store.lock().await.insert(query_id, result);
+}
+
+/// Export the buffered result for `query_id` to `path` in the requested format.
+pub async fn export_result(
+    store: &ResultStore,
+    query_id: &str,
+    format: &str,
+    path: &str,
+) -> Result<(), String> {
+    let result = store
+        .lock()
+        .await
+        .get(query_id)
+        .cloned()
+        .ok_or_else(|| format!("no buffered result for query '{}'", query_id))?;
+
+    match format.to_ascii_lowercase().as_str() {
+        "csv" => write_csv(&result, path),
+        "jsonl" | "json-lines" | "ndjson" => write_jsonl(&result, path),
+        "arrow" | "ipc" => write_arrow(&result, path),
+        other => Err(format!("unsupported export format: {}", other)),
+    }
+}
+
+/// RFC-4180 CSV with a header row and minimal quoting.
+fn write_csv(result: &StoredResult, path: &str) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut out = BufWriter::new(file);
+
+    let header = result
+        .columns
+        .iter()
+        .map(|c| csv_field(&c.name))
+        .collect::<Vec<_>>()
+        .join(",");
+    write!(out, "{}\r\n", header).map_err(|e| e.to_string())?;
+
+    for row in &result.rows {
+        let line = result
+            .columns
+            .iter()
+            .map(|c| csv_field(&scalar_to_string(row.get(&c.name))))
+            .collect::<Vec<_>>()
+            .join(",");
+        write!(out, "{}\r\n", line).map_err(|e| e.to_string())?;
+    }
+    out.flush().map_err(|e| e.to_string())
+}
+
+/// Quote a CSV field when it contains a delimiter, quote, or newline, doubling
+/// any embedded quotes per RFC 4180.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Render a JSON scalar as the plain text used in CSV cells.
+fn scalar_to_string(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Bool(b)) => b.to_string(),
+        Some(Value::Number(n)) => n.to_string(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Newline-delimited JSON, one object per row.
+fn write_jsonl(result: &StoredResult, path: &str) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut out = BufWriter::new(file);
+    for row in &result.rows {
+        let line = serde_json::to_string(row).map_err(|e| e.to_string())?;
+        writeln!(out, "{}", line).map_err(|e| e.to_string())?;
+    }
+    out.flush().map_err(|e| e.to_string())
+}
+
+/// Map a declared SQL type name onto the Arrow datatype used for its column.
+fn arrow_type(type_name: &str) -> DataType {
+    let upper = type_name.to_ascii_uppercase();
+    if upper.contains("INT") {
+        DataType::Int64
+    } else if upper.contains("REAL")
+        || upper.contains("FLOAT")
+        || upper.contains("DOUBLE")
+        || upper.contains("NUMERIC")
+    {
+        DataType::Float64
+    } else if upper.contains("BOOL") {
+        DataType::Boolean
+    } else if upper.contains("BLOB") || upper.contains("BYTEA") {
+        DataType::Binary
+    } else {
+        DataType::Utf8
+    }
+}
+
+/// Apache Arrow IPC file built from one `RecordBatch` over the buffered rows.
+fn write_arrow(result: &StoredResult, path: &str) -> Result<(), String> {
+    let fields: Vec<Field> = result
+        .columns
+        .iter()
+        .map(|c| Field::new(&c.name, arrow_type(&c.type_name), true))
+        .collect();
+    let schema = Arc::new(Schema::new(fields));
+
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(result.columns.len());
+    for col in &result.columns {
+        let cells = result.rows.iter().map(|r| r.get(&col.name));
+        arrays.push(build_array(arrow_type(&col.type_name), cells));
+    }
+
+    let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(|e| e.to_string())?;
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut writer = FileWriter::try_new(file, &schema).map_err(|e| e.to_string())?;
+    writer.write(&batch).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())
+}
+
+/// Recover the raw bytes of a blob cell for Arrow's `Binary` column. Blobs are
+/// carried through JSON as base64 — either as a plain string (how `value_to_json`
+/// renders them) or as a `{ "$blob": "<base64>" }` tagged object — so both are
+/// decoded back to the original bytes rather than stored as their ASCII text.
+fn blob_bytes(cell: Option<&Value>) -> Option<Vec<u8>> {
+    match cell {
+        Some(Value::String(s)) => Some(crate::base64_decode(s)),
+        Some(Value::Object(map)) => map
+            .get("$blob")
+            .and_then(Value::as_str)
+            .map(crate::base64_decode),
+        _ => None,
+    }
+}
+
+/// Build a typed Arrow array from an iterator of JSON cell values.
+fn build_array<'a, I>(datatype: DataType, cells: I) -> ArrayRef
+where
+    I: Iterator<Item = Option<&'a Value>>,
+{
+    match datatype {
+        DataType::Int64 => {
+            let mut b = Int64Builder::new();
+            for cell in cells {
+                b.append_option(cell.and_then(|v| v.as_i64()));
+            }
+            Arc::new(b.finish())
+        }
+        DataType::Float64 => {
+            let mut b = Float64Builder::new();
+            for cell in cells {
+                b.append_option(cell.and_then(|v| v.as_f64()));
+            }
+            Arc::new(b.finish())
+        }
+        DataType::Boolean => {
+            let mut b = BooleanBuilder::new();
+            for cell in cells {
+                b.append_option(cell.and_then(|v| v.as_bool()));
+            }
+            Arc::new(b.finish())
+        }
+        DataType::Binary => {
+            let mut b = BinaryBuilder::new();
+            for cell in cells {
+                match blob_bytes(cell) {
+                    Some(bytes) => b.append_value(&bytes),
+                    None => b.append_null(),
+                }
+            }
+            Arc::new(b.finish())
+        }
+        _ => {
+            let mut b = StringBuilder::new();
+            for cell in cells {
+                match cell {
+                    None | Some(Value::Null) => b.append_null(),
+                    Some(Value::String(s)) => b.append_value(s),
+                    Some(other) => b.append_value(other.to_string()),
+                }
+            }
+            Arc::new(b.finish())
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Schema introspection for the object explorer and autocomplete.
+//!
+//! [`introspect`] walks the connected database's catalog and returns a JSON tree
+//! of tables, each with its columns (name, declared type, nullability, primary
+//! key flag) and the names of its indexes. Results are cached per connection in
+//! [`SchemaCache`] and invalidated after any successful DDL so the UI can offer
+//! completions without re-scanning on every keystroke.
+
+use crate::connections::{Backend, Connection};
+use serde_json::{json, Value};
+use sqlx::{AnyPool, Row};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Cache of introspected schema trees, keyed by connection name.
+pub type SchemaCache = Arc<RwLock<HashMap<String, Value>>>;
+
+/// Build an empty schema cache for `.manage()`.
+pub fn new_cache() -> SchemaCache {
+    Arc::new(RwLock::new(HashMap::new()))
+}
+
+/// Drop the cached schema for a connection, forcing the next introspection to
+/// re-scan the catalog. Called after DDL changes the shape of the database.
+pub async fn invalidate(cache: &SchemaCache, connection: &str) {
+    cache.write().await.remove(connection);
+}
+
+/// Return the schema tree for `connection`, introspecting and caching it on a
+/// miss.
+pub async fn cached_introspect(
+    cache: &SchemaCache,
+    connection: &str,
+    conn: &Connection,
+) -> Result<Value, String> {
+    if let Some(cached) = cache.read().await.get(connection) {
+        return Ok(cached.clone());
+    }
+    let tree = introspect(conn).await?;
+    cache
+        .write()
+        .await
+        .insert(connection.to_string(), tree.clone());
+    Ok(tree)
+}
+
+/// Introspect the catalog of `conn`, dispatching on its backend.
+pub async fn introspect(conn: &Connection) -> Result<Value, String> {
+    match conn.backend {
+        Backend::Postgres => introspect_postgres(&conn.pool).await,
+        Backend::Sqlite => introspect_sqlite(&conn.pool).await,
+    }
+}
+
+/// Escape a single-quoted SQL string literal for interpolation into a PRAGMA.
+fn quote_literal(value: &str) -> String {
+    value.replace('\'', "''")
+}
+
+async fn introspect_sqlite(pool: &AnyPool) -> Result<Value, String> {
+    let table_rows =
+        sqlx::query("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    let mut tables = Vec::with_capacity(table_rows.len());
+    for table_row in &table_rows {
+        let name: String = table_row.get("name");
+        if name.starts_with("sqlite_") {
+            continue;
+        }
+        let quoted = quote_literal(&name);
+
+        let col_rows = sqlx::query(&format!("PRAGMA table_info('{}')", quoted))
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        let columns: Vec<Value> = col_rows
+            .iter()
+            .map(|c| {
+                json!({
+                    "name": c.get::<String, _>("name"),
+                    "type": c.get::<String, _>("type"),
+                    "nullable": c.get::<i64, _>("notnull") == 0,
+                    "primary_key": c.get::<i64, _>("pk") > 0,
+                })
+            })
+            .collect();
+
+        let index_rows = sqlx::query(&format!("PRAGMA index_list('{}')", quoted))
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        let indexes: Vec<String> = index_rows.iter().map(|i| i.get::<String, _>("name")).collect();
+
+        tables.push(json!({ "name": name, "columns": columns, "indexes": indexes }));
+    }
+
+    Ok(json!({ "tables": tables }))
+}
+
+async fn introspect_postgres(pool: &AnyPool) -> Result<Value, String> {
+    // `is_pk` is derived with a correlated EXISTS rather than a join through
+    // key_column_usage: a column belonging to several key constraints (a PK that
+    // is also an FK, or part of a composite UNIQUE) would otherwise fan out into
+    // duplicate rows, with the PK flag landing on the wrong copy.
+    let col_rows = sqlx::query(
+        "SELECT c.table_name, c.column_name, c.data_type, c.is_nullable,
+                EXISTS (
+                    SELECT 1
+                      FROM information_schema.key_column_usage kcu
+                      JOIN information_schema.table_constraints tc
+                        ON tc.constraint_name = kcu.constraint_name
+                       AND tc.constraint_schema = kcu.constraint_schema
+                     WHERE tc.constraint_type = 'PRIMARY KEY'
+                       AND kcu.table_schema = c.table_schema
+                       AND kcu.table_name = c.table_name
+                       AND kcu.column_name = c.column_name
+                ) AS is_pk
+           FROM information_schema.columns c
+          WHERE c.table_schema = 'public'
+          ORDER BY c.table_name, c.ordinal_position",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Preserve table discovery order while grouping columns.
+    let mut order: Vec<String> = Vec::new();
+    let mut grouped: HashMap<String, Vec<Value>> = HashMap::new();
+    for row in &col_rows {
+        let table: String = row.get("table_name");
+        let column = json!({
+            "name": row.get::<String, _>("column_name"),
+            "type": row.get::<String, _>("data_type"),
+            "nullable": row.get::<String, _>("is_nullable") == "YES",
+            "primary_key": row.try_get::<bool, _>("is_pk").unwrap_or(false),
+        });
+        grouped.entry(table.clone()).or_default().push(column);
+        if !order.contains(&table) {
+            order.push(table);
+        }
+    }
+
+    let index_rows = sqlx::query(
+        "SELECT tablename, indexname FROM pg_indexes WHERE schemaname = 'public'",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let mut indexes: HashMap<String, Vec<String>> = HashMap::new();
+    for row in &index_rows {
+        indexes
+            .entry(row.get::<String, _>("tablename"))
+            .or_default()
+            .push(row.get::<String, _>("indexname"));
+    }
+
+    let tables: Vec<Value> = order
+        .into_iter()
+        .map(|name| {
+            let columns = grouped.remove(&name).unwrap_or_default();
+            let idx = indexes.remove(&name).unwrap_or_default();
+            json!({ "name": name, "columns": columns, "indexes": idx })
+        })
+        .collect();
+
+    Ok(json!({ "tables": tables }))
+}
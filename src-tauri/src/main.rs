@@ -1,56 +1,427 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod connections;
+mod export;
+mod introspect;
+mod migrations;
+mod stream;
+
+use connections::ConnectionRegistry;
+use export::ResultStore;
+use introspect::SchemaCache;
+use stream::InflightQueries;
+use serde_json::{json, Map, Value};
+use sqlx::any::AnyRow;
+use sqlx::{Column, Row, TypeInfo, ValueRef};
+use tauri::command;
+
+/// The name of the built-in connection backing startup migrations and the
+/// initial query target before the user registers any connections of their own.
+const DEFAULT_CONNECTION: &str = "default";
+
 // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
-fn main() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_process::init())
-        .invoke_handler(tauri::generate_handler![greet])
-        .invoke_handler(tauri::generate_handler![execute_sql])
-        // .invoke_handler(tauri::generate_handler![execute_sql])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+/// Resolve the on-disk location of the SQLite database under the user's data
+/// directory, creating the parent directory if it does not yet exist.
+fn database_path() -> Result<std::path::PathBuf, String> {
+    let mut dir = tauri::api::path::data_dir()
+        .ok_or_else(|| "could not resolve the user data directory".to_string())?;
+    dir.push("VizierCells");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("could not create data directory: {}", e))?;
+    dir.push("vizier.db");
+    Ok(dir)
 }
 
+/// Decode a single value (referenced by column index) from an `AnyRow` into the
+/// JSON value the frontend grid can render. The driver-reported type name is
+/// used to pick a decode target, covering both SQLite and Postgres spellings.
+fn value_to_json(row: &AnyRow, idx: usize) -> Value {
+    let raw = match row.try_get_raw(idx) {
+        Ok(raw) => raw,
+        Err(_) => return Value::Null,
+    };
+    if raw.is_null() {
+        return Value::Null;
+    }
+    let name = raw.type_info().name().to_ascii_uppercase();
+    if name.contains("INT") || name == "BIGINT" {
+        row.try_get::<i64, _>(idx)
+            .map(|v| json!(v))
+            .unwrap_or(Value::Null)
+    } else if name.contains("REAL")
+        || name.contains("FLOAT")
+        || name.contains("DOUBLE")
+        || name.contains("NUMERIC")
+    {
+        row.try_get::<f64, _>(idx)
+            .map(|v| json!(v))
+            .unwrap_or(Value::Null)
+    } else if name.contains("BOOL") {
+        row.try_get::<bool, _>(idx)
+            .map(|v| json!(v))
+            .unwrap_or(Value::Null)
+    } else if name.contains("BLOB") || name.contains("BYTEA") {
+        row.try_get::<Vec<u8>, _>(idx)
+            .map(|v| json!(base64_encode(&v)))
+            .unwrap_or(Value::Null)
+    } else {
+        row.try_get::<String, _>(idx)
+            .map(Value::String)
+            .unwrap_or(Value::Null)
+    }
+}
 
+/// Minimal standard base64 encoder so BLOB columns survive the JSON round-trip.
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as usize) << 16 | (b[1] as usize) << 8 | b[2] as usize;
+        out.push(ALPHABET[(n >> 18) & 0x3f] as char);
+        out.push(ALPHABET[(n >> 12) & 0x3f] as char);
+        out.push(if chunk.len() > 1 {
+            ALPHABET[(n >> 6) & 0x3f] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            ALPHABET[n & 0x3f] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
 
-use serde_json::json;
-use tauri::command;
+/// Whether a statement is expected to return a row set rather than an
+/// affected-rows count.
+fn statement_returns_rows(sql: &str) -> bool {
+    let t = sql.trim_start();
+    t.get(..6).map_or(false, |s| s.eq_ignore_ascii_case("select"))
+        || t.get(..4).map_or(false, |s| s.eq_ignore_ascii_case("with"))
+        || t.get(..7).map_or(false, |s| s.eq_ignore_ascii_case("pragma "))
+}
+
+/// Whether a statement is data-definition (changes schema shape), so that any
+/// cached introspection for the connection must be invalidated afterwards.
+pub(crate) fn statement_is_ddl(sql: &str) -> bool {
+    let head = sql.trim_start();
+    ["create", "alter", "drop", "truncate"]
+        .iter()
+        .any(|kw| head.get(..kw.len()).map_or(false, |s| s.eq_ignore_ascii_case(kw)))
+}
+
+/// Serialize a single row into a JSON object keyed by column name.
+pub(crate) fn row_to_object(row: &AnyRow) -> Value {
+    let mut obj = Map::new();
+    for (idx, col) in row.columns().iter().enumerate() {
+        obj.insert(col.name().to_string(), value_to_json(row, idx));
+    }
+    Value::Object(obj)
+}
+
+/// Serialize a fetched row set into the `{ columns, rows, rows_affected }`
+/// envelope the frontend grid renders.
+fn rows_to_json(rows: &[AnyRow]) -> Value {
+    let columns: Vec<String> = rows
+        .first()
+        .map(|row| row.columns().iter().map(|c| c.name().to_string()).collect())
+        .unwrap_or_default();
+
+    let out_rows: Vec<Value> = rows.iter().map(row_to_object).collect();
+    json!({ "columns": columns, "rows": out_rows, "rows_affected": 0 })
+}
 
 #[command]
-fn execute_sql(sql: String) -> Result<String, String> {
-    println!("Executing SQL: {}", sql);
-    
-    // Return hardcoded results based on the SQL query
-    let result = if sql.contains("SELECT * FROM users") {
-        json!([
-            {"id": 1, "name": "John Doe", "email": "john@example.com", "age": 30},
-            {"id": 2, "name": "Jane Smith", "email": "jane@example.com", "age": 25},
-            {"id": 3, "name": "Bob Johnson", "email": "bob@example.com", "age": 35},
-            {"id": 4, "name": "Alice Brown", "email": "alice@example.com", "age": 28},
-            {"id": 5, "name": "Charlie Wilson", "email": "charlie@example.com", "age": 32}
-        ])
-    } else if sql.contains("COUNT") {
-        json!([{"user_count": 5}])
-    } else if sql.contains("INSERT") {
-        json!({"message": "Insert successful", "rows_affected": 1})
-    } else if sql.contains("UPDATE") {
-        json!({"message": "Update successful", "rows_affected": 1})
-    } else if sql.contains("DELETE") {
-        json!({"message": "Delete successful", "rows_affected": 1})
-    } else if sql.contains("CREATE TABLE") {
-        json!({"message": "Table created successfully"})
-    } else if sql.trim().is_empty() {
+async fn execute_sql(
+    connection: String,
+    sql: String,
+    registry: tauri::State<'_, ConnectionRegistry>,
+    cache: tauri::State<'_, SchemaCache>,
+) -> Result<Value, String> {
+    println!("[{}] Executing SQL: {}", connection, sql);
+    if sql.trim().is_empty() {
         return Err("Empty SQL query".to_string());
+    }
+    let conn = connections::pool_for(&registry, &connection).await?;
+
+    if statement_returns_rows(&sql) {
+        let rows = sqlx::query(&sql)
+            .fetch_all(&conn.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(rows_to_json(&rows))
     } else {
-        json!({"error": "Query not supported in demo", "received_query": sql})
-    };
-    
-    Ok(result.to_string())
+        let result = sqlx::query(&sql)
+            .execute(&conn.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        if statement_is_ddl(&sql) {
+            introspect::invalidate(&cache, &connection).await;
+        }
+        Ok(json!({
+            "columns": [],
+            "rows": [],
+            "rows_affected": result.rows_affected(),
+        }))
+    }
+}
+
+/// Bind a JSON value onto an `Any` query as the appropriate SQL type.
+fn bind_json<'q>(
+    query: sqlx::query::Query<'q, sqlx::Any, sqlx::any::AnyArguments<'q>>,
+    value: &Value,
+) -> sqlx::query::Query<'q, sqlx::Any, sqlx::any::AnyArguments<'q>> {
+    match value {
+        Value::Null => query.bind(Option::<String>::None),
+        Value::Bool(b) => query.bind(*b),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                query.bind(i)
+            } else {
+                query.bind(n.as_f64().unwrap_or(0.0))
+            }
+        }
+        Value::String(s) => query.bind(s.clone()),
+        // A blob is passed as a tagged object `{ "$blob": "<base64>" }`; the
+        // base64 payload is decoded back to raw bytes and bound as a BLOB, the
+        // inverse of how `value_to_json` renders blob columns on the way out.
+        Value::Object(map) if map.len() == 1 && map.contains_key("$blob") => {
+            match map.get("$blob").and_then(Value::as_str) {
+                Some(b64) => query.bind(base64_decode(b64)),
+                None => query.bind(Option::<Vec<u8>>::None),
+            }
+        }
+        // Other arrays/objects are not valid scalar binds; encode them as JSON text.
+        other => query.bind(other.to_string()),
+    }
+}
+
+/// Decode standard base64 back into raw bytes, ignoring padding and any
+/// non-alphabet characters (whitespace, newlines). The inverse of
+/// [`base64_encode`]; invalid input simply yields the bytes decoded so far.
+pub(crate) fn base64_decode(s: &str) -> Vec<u8> {
+    fn val(c: u8) -> Option<u8> {
+        match c {
+            b'A'..=b'Z' => Some(c - b'A'),
+            b'a'..=b'z' => Some(c - b'a' + 26),
+            b'0'..=b'9' => Some(c - b'0' + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+    let mut out = Vec::with_capacity(s.len() / 4 * 3);
+    let mut acc = 0u32;
+    let mut bits = 0u8;
+    for &c in s.as_bytes() {
+        let Some(v) = val(c) else { continue };
+        acc = (acc << 6) | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    out
+}
+
+#[command]
+async fn execute_sql_params(
+    connection: String,
+    sql: String,
+    params: Vec<Value>,
+    registry: tauri::State<'_, ConnectionRegistry>,
+    cache: tauri::State<'_, SchemaCache>,
+) -> Result<Value, String> {
+    println!("[{}] Executing parameterized SQL: {}", connection, sql);
+    if sql.trim().is_empty() {
+        return Err("Empty SQL query".to_string());
+    }
+    let conn = connections::pool_for(&registry, &connection).await?;
+    let rewritten = connections::rewrite_placeholders(&sql, conn.backend);
+
+    let mut query = sqlx::query(&rewritten);
+    for param in &params {
+        query = bind_json(query, param);
+    }
+
+    if statement_returns_rows(&sql) {
+        let rows = query.fetch_all(&conn.pool).await.map_err(|e| e.to_string())?;
+        Ok(rows_to_json(&rows))
+    } else {
+        let result = query.execute(&conn.pool).await.map_err(|e| e.to_string())?;
+        if statement_is_ddl(&sql) {
+            introspect::invalidate(&cache, &connection).await;
+        }
+        Ok(json!({
+            "columns": [],
+            "rows": [],
+            "rows_affected": result.rows_affected(),
+        }))
+    }
+}
+
+/// Start a streamed query, emitting `sql-batch` events to the window.
+#[command]
+async fn execute_sql_stream(
+    window: tauri::Window,
+    query_id: String,
+    sql: String,
+    connection: String,
+    registry: tauri::State<'_, ConnectionRegistry>,
+    inflight: tauri::State<'_, InflightQueries>,
+    results: tauri::State<'_, ResultStore>,
+    cache: tauri::State<'_, SchemaCache>,
+) -> Result<(), String> {
+    stream::execute_sql_stream(
+        window,
+        registry.inner().clone(),
+        inflight.inner().clone(),
+        results.inner().clone(),
+        cache.inner().clone(),
+        query_id,
+        sql,
+        connection,
+    )
+    .await
+}
+
+/// Cancel an in-flight streamed query by id.
+#[command]
+async fn cancel_query(
+    query_id: String,
+    inflight: tauri::State<'_, InflightQueries>,
+) -> Result<(), String> {
+    stream::cancel_query(&inflight, &query_id).await
+}
+
+/// Export a buffered query result to CSV, JSON-lines, or Arrow IPC.
+#[command]
+async fn export_result(
+    query_id: String,
+    format: String,
+    path: String,
+    results: tauri::State<'_, ResultStore>,
+) -> Result<(), String> {
+    export::export_result(&results, &query_id, &format, &path).await
+}
+
+/// Return the schema tree (tables, columns, indexes) for a connection, using a
+/// cached result when available.
+#[command]
+async fn introspect_schema(
+    connection: String,
+    registry: tauri::State<'_, ConnectionRegistry>,
+    cache: tauri::State<'_, SchemaCache>,
+) -> Result<Value, String> {
+    let conn = connections::pool_for(&registry, &connection).await?;
+    introspect::cached_introspect(&cache, &connection, &conn).await
+}
+
+/// Register a named connection (SQLite file path or Postgres URL).
+#[command]
+async fn add_connection(
+    name: String,
+    kind: String,
+    url: String,
+    registry: tauri::State<'_, ConnectionRegistry>,
+) -> Result<(), String> {
+    connections::add_connection(&registry, name, kind, url).await
+}
+
+/// List the names of all registered connections.
+#[command]
+async fn list_connections(
+    registry: tauri::State<'_, ConnectionRegistry>,
+) -> Result<Vec<String>, String> {
+    Ok(connections::list_connections(&registry).await)
+}
+
+/// Remove a named connection, closing its pool.
+#[command]
+async fn remove_connection(
+    name: String,
+    registry: tauri::State<'_, ConnectionRegistry>,
+) -> Result<(), String> {
+    connections::remove_connection(&registry, &name).await
+}
+
+/// Apply any outstanding schema migrations, returning the newly applied versions
+/// so the UI can surface bootstrap progress.
+#[command]
+async fn run_migrations(registry: tauri::State<'_, ConnectionRegistry>) -> Result<Vec<i64>, String> {
+    let conn = connections::pool_for(&registry, DEFAULT_CONNECTION).await?;
+    migrations::run_migrations(&conn.pool).await
+}
+
+/// Report the current (highest applied) schema version, if any.
+#[command]
+async fn schema_version(registry: tauri::State<'_, ConnectionRegistry>) -> Result<Option<i64>, String> {
+    let conn = connections::pool_for(&registry, DEFAULT_CONNECTION).await?;
+    migrations::current_version(&conn.pool).await
+}
+
+fn main() {
+    sqlx::any::install_default_drivers();
+
+    // Expose the bootstrapped SQLite database as the "default" connection so the
+    // workbench has something to query against before the user registers more,
+    // and run startup migrations against that same pool rather than opening a
+    // second handle to the same file (which would contend on SQLite's lock).
+    let registry = connections::new_registry();
+    let default_url = format!(
+        "sqlite://{}?mode=rwc",
+        database_path().expect("data directory").display()
+    );
+    tauri::async_runtime::block_on(connections::add_connection(
+        &registry,
+        DEFAULT_CONNECTION.to_string(),
+        "sqlite".to_string(),
+        default_url,
+    ))
+    .expect("failed to register the default connection");
+
+    let default_conn = tauri::async_runtime::block_on(connections::pool_for(
+        &registry,
+        DEFAULT_CONNECTION,
+    ))
+    .expect("default connection missing");
+    tauri::async_runtime::block_on(migrations::run_migrations(&default_conn.pool))
+        .expect("failed to run startup migrations");
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_process::init())
+        .manage(registry)
+        .manage(stream::new_inflight())
+        .manage(export::new_store())
+        .manage(introspect::new_cache())
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            execute_sql,
+            execute_sql_params,
+            execute_sql_stream,
+            cancel_query,
+            export_result,
+            introspect_schema,
+            add_connection,
+            list_connections,
+            remove_connection,
+            run_migrations,
+            schema_version
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
 }
@@ -0,0 +1,88 @@
+//! Schema bootstrap for VizierCells.
+//!
+//! Versioned migration files live under `migrations/` as `NNNN_description.sql`
+//! and are embedded at compile time with [`sqlx::migrate!`]. At startup we apply
+//! any migrations that have not yet run, recording each in a `_vizier_migrations`
+//! table together with its checksum so tampering with an already-applied file is
+//! detected and refused rather than silently re-run.
+
+use sqlx::migrate::Migrator;
+use sqlx::{AnyPool, Executor, Row};
+
+/// The embedded set of migrations, resolved relative to the crate root.
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Ensure the bookkeeping table used to track applied migrations exists.
+async fn ensure_log_table(pool: &AnyPool) -> Result<(), String> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS _vizier_migrations (
+            version    INTEGER PRIMARY KEY,
+            checksum   BLOB NOT NULL,
+            applied_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )",
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Apply every embedded migration that has not yet been recorded, returning the
+/// versions that were newly applied in ascending order.
+///
+/// Fails fast if a migration that was previously applied now has a different
+/// checksum on disk, since that means history has been rewritten under us.
+pub async fn run_migrations(pool: &AnyPool) -> Result<Vec<i64>, String> {
+    ensure_log_table(pool).await?;
+
+    let mut applied = Vec::new();
+    for migration in MIGRATOR.iter() {
+        let version = migration.version;
+        let recorded: Option<Vec<u8>> =
+            sqlx::query("SELECT checksum FROM _vizier_migrations WHERE version = ?")
+                .bind(version)
+                .fetch_optional(pool)
+                .await
+                .map_err(|e| e.to_string())?
+                .map(|row| row.get::<Vec<u8>, _>("checksum"));
+
+        match recorded {
+            Some(checksum) => {
+                if checksum != *migration.checksum {
+                    return Err(format!(
+                        "checksum mismatch for migration {}: the file has changed since it was applied",
+                        version
+                    ));
+                }
+            }
+            None => {
+                // Run the file through the raw (unprepared) executor path so
+                // migrations containing more than one statement are applied in
+                // full; `query(..).execute` would prepare and run only the first.
+                pool.execute(migration.sql.as_ref())
+                    .await
+                    .map_err(|e| format!("migration {} failed: {}", version, e))?;
+                sqlx::query("INSERT INTO _vizier_migrations (version, checksum) VALUES (?, ?)")
+                    .bind(version)
+                    .bind(&*migration.checksum)
+                    .execute(pool)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                applied.push(version);
+            }
+        }
+    }
+    Ok(applied)
+}
+
+/// Report the highest applied migration version, or `None` if the database has
+/// not been bootstrapped yet.
+pub async fn current_version(pool: &AnyPool) -> Result<Option<i64>, String> {
+    ensure_log_table(pool).await?;
+    let version: Option<i64> = sqlx::query("SELECT MAX(version) AS v FROM _vizier_migrations")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .get("v");
+    Ok(version)
+}
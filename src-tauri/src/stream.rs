@@ -0,0 +1,168 @@
+//! Streaming query execution for large result sets.
+//!
+//! Rather than materialising a whole result into one JSON blob, a streamed
+//! query runs against the pool with `sqlx`'s row stream and pushes fixed-size
+//! batches to the frontend through the Tauri event system. In-flight queries
+//! are tracked by `query_id` so a runaway scan can be cancelled mid-flight.
+
+use crate::connections::{pool_for, ConnectionRegistry};
+use crate::export::{store as store_result, ColumnMeta, ResultStore, StoredResult};
+use crate::introspect::{invalidate, SchemaCache};
+use crate::{row_to_object, statement_is_ddl};
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use sqlx::{Column, Row, TypeInfo};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::async_runtime::JoinHandle;
+use tauri::Emitter;
+use tokio::sync::Mutex;
+
+/// Number of rows gathered before a `sql-batch` event is emitted.
+const BATCH_SIZE: usize = 500;
+
+/// Upper bound on rows retained for later export. Streaming exists precisely so
+/// an unbounded scan does not have to be held in memory, so the export buffer is
+/// capped: once a query streams past this many rows it is treated as too large
+/// to re-serialize and no buffer is kept (export then reports it unavailable).
+const EXPORT_BUFFER_CAP: usize = 100_000;
+
+/// Registry of running streamed queries, keyed by caller-supplied `query_id`,
+/// so they can be aborted on request.
+pub type InflightQueries = Arc<Mutex<HashMap<String, JoinHandle<()>>>>;
+
+/// Build an empty in-flight registry for `.manage()`.
+pub fn new_inflight() -> InflightQueries {
+    Arc::new(Mutex::new(HashMap::new()))
+}
+
+/// Spawn a streamed query, emitting `sql-batch` events as rows arrive and a
+/// final event carrying the total row count and elapsed time.
+pub async fn execute_sql_stream(
+    window: tauri::Window,
+    registry: ConnectionRegistry,
+    inflight: InflightQueries,
+    results: ResultStore,
+    cache: SchemaCache,
+    query_id: String,
+    sql: String,
+    connection: String,
+) -> Result<(), String> {
+    if sql.trim().is_empty() {
+        return Err("Empty SQL query".to_string());
+    }
+    let pool = pool_for(&registry, &connection).await?.pool;
+    let inflight_for_task = inflight.clone();
+    let id = query_id.clone();
+    let conn = connection.clone();
+
+    // Hold the registry lock across spawn and insert so the handle is recorded
+    // before the task can self-remove on completion, and so a reused `query_id`
+    // aborts the query it would otherwise overwrite rather than leaking it.
+    let mut guard = inflight.lock().await;
+    if let Some(previous) = guard.remove(&query_id) {
+        previous.abort();
+    }
+
+    let handle = tauri::async_runtime::spawn(async move {
+        let started = Instant::now();
+        let mut stream = sqlx::query(&sql).fetch(&pool);
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        // Rows are retained for later export only up to `EXPORT_BUFFER_CAP`; past
+        // that the buffer is dropped so a runaway scan streams without growing it.
+        let mut buffered: Option<Vec<serde_json::Map<String, Value>>> = Some(Vec::new());
+        let mut columns: Vec<ColumnMeta> = Vec::new();
+        let mut total: u64 = 0;
+
+        loop {
+            match stream.next().await {
+                Some(Ok(row)) => {
+                    if columns.is_empty() {
+                        columns = row
+                            .columns()
+                            .iter()
+                            .map(|c| ColumnMeta {
+                                name: c.name().to_string(),
+                                type_name: c.type_info().name().to_string(),
+                            })
+                            .collect();
+                    }
+                    if let Value::Object(obj) = row_to_object(&row) {
+                        match &mut buffered {
+                            Some(buf) if buf.len() < EXPORT_BUFFER_CAP => buf.push(obj.clone()),
+                            // Over the cap: drop the buffer and stop retaining.
+                            slot => *slot = None,
+                        }
+                        batch.push(Value::Object(obj));
+                    }
+                    total += 1;
+                    if batch.len() >= BATCH_SIZE {
+                        let _ = window.emit(
+                            "sql-batch",
+                            json!({ "query_id": id, "rows": batch, "done": false }),
+                        );
+                        batch = Vec::with_capacity(BATCH_SIZE);
+                    }
+                }
+                Some(Err(e)) => {
+                    let _ = window.emit(
+                        "sql-batch",
+                        json!({ "query_id": id, "error": e.to_string(), "done": true }),
+                    );
+                    break;
+                }
+                None => {
+                    // Only register an export buffer when the whole result fit
+                    // under the cap; otherwise export reports it unavailable.
+                    if let Some(rows) = buffered.take() {
+                        store_result(
+                            &results,
+                            id.clone(),
+                            StoredResult {
+                                columns: columns.clone(),
+                                rows,
+                            },
+                        )
+                        .await;
+                    } else {
+                        results.lock().await.remove(&id);
+                    }
+                    // A streamed DDL statement changes the schema shape, so the
+                    // cached introspection for this connection must be dropped.
+                    if statement_is_ddl(&sql) {
+                        invalidate(&cache, &conn).await;
+                    }
+                    let _ = window.emit(
+                        "sql-batch",
+                        json!({
+                            "query_id": id,
+                            "rows": batch,
+                            "done": true,
+                            "total_rows": total,
+                            "elapsed_ms": started.elapsed().as_millis() as u64,
+                        }),
+                    );
+                    break;
+                }
+            }
+        }
+
+        inflight_for_task.lock().await.remove(&id);
+    });
+
+    guard.insert(query_id, handle);
+    drop(guard);
+    Ok(())
+}
+
+/// Abort an in-flight streamed query, dropping its stream and spawned task.
+pub async fn cancel_query(inflight: &InflightQueries, query_id: &str) -> Result<(), String> {
+    match inflight.lock().await.remove(query_id) {
+        Some(handle) => {
+            handle.abort();
+            Ok(())
+        }
+        None => Err(format!("no in-flight query with id '{}'", query_id)),
+    }
+}